@@ -1,27 +1,102 @@
-use crate::ast::symbol::{ModSymTable, ScopedInsertErr, ScopedSymbolStack};
-use crate::ast::{Expr, ExprKind, Function, Item, ItemKind, Module};
+use crate::ast::symbol::{
+    ModSymTable, Namespace, ScopeKind, ScopedInsertErr, ScopedSymbolStack, SymbolKind,
+};
+use crate::ast::{Expr, ExprKind, Function, Item, ItemKind, Module, ModuleId, Span, TypeAnno};
 use crate::ast_pass::ModulePass;
 
 pub struct AstNameResolver<'s> {
     scopes: ScopedSymbolStack<'s>,
-    errs: Vec<ScopedInsertErr>,
+    errs: Vec<AstResolutionErr>,
+    modules: ModuleRegistry<'s>,
+}
+
+/// The set of already-resolved modules an `AstNameResolver` may import from,
+/// keyed by module id. Populated by the driver as it resolves the module graph
+/// in dependency order.
+#[derive(Default)]
+pub struct ModuleRegistry<'s> {
+    resolved: Vec<(ModuleId, ModSymTable)>,
+    _src: std::marker::PhantomData<&'s str>,
+}
+
+impl<'s> ModuleRegistry<'s> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a resolved module's symbol table so later modules can import it.
+    pub fn insert(&mut self, id: ModuleId, table: ModSymTable) {
+        self.resolved.push((id, table));
+    }
+
+    fn get(&self, id: ModuleId) -> Option<&ModSymTable> {
+        self.resolved
+            .iter()
+            .find(|(mid, _)| *mid == id)
+            .map(|(_, table)| table)
+    }
 }
 
 pub enum AstResolutionErr {
-    Redefinition,
+    /// A name was inserted twice into the same scope.
+    Redefinition(ScopedInsertErr),
+    /// A `Var`/callee referenced a name that is not in scope. `suggestion`
+    /// carries the span of the closest in-scope identifier, when one is near
+    /// enough to be a plausible typo.
+    UnresolvedName {
+        span: Span,
+        suggestion: Option<Span>,
+    },
+    /// A group of `let` bindings refer to one another cyclically (a mutual
+    /// cycle, or a self-referential non-function binding). `spans` carries the
+    /// bound name of every binding in the offending strongly-connected group.
+    RecursiveBindingCycle {
+        spans: Vec<Span>,
+    },
+    /// A `use` item named a module or symbol that could not be resolved against
+    /// the module registry. `span` is the whole import when the module is
+    /// unknown, or the imported name when the symbol is not found.
+    UnresolvedImport {
+        span: Span,
+    },
     // ReturnBeforeBlockEnd,
 }
-pub type NameResolutionResult = Result<ModSymTable, Vec<ScopedInsertErr>>;
+
+impl From<ScopedInsertErr> for AstResolutionErr {
+    fn from(e: ScopedInsertErr) -> Self {
+        Self::Redefinition(e)
+    }
+}
+
+/// A non-fatal diagnostic produced by name resolution.
+pub enum AstResolutionWarning {
+    /// A local or argument was introduced but never read. `span` is the
+    /// binding's stored definition span.
+    UnusedBinding { span: Span },
+}
+
+/// A successfully resolved module: the symbol table plus any warning-level
+/// diagnostics gathered along the way.
+pub struct ResolvedModule {
+    pub table: ModSymTable,
+    pub warnings: Vec<AstResolutionWarning>,
+}
+
+pub type NameResolutionResult = Result<ResolvedModule, Vec<AstResolutionErr>>;
 
 impl<'s> ModulePass<'s> for AstNameResolver<'s> {
     type Output = NameResolutionResult;
     fn run_pass(mut self, m: &Module<'s>) -> Self::Output {
         // root scope
-        self.scopes.push_scope();
+        self.scopes.push_scope(ScopeKind::Block);
 
         // Phase 1, register all top-level symbols
         self.resolve_top_level_names(&m.body);
 
+        // Phase 1b, pull in imported symbols before resolving any bodies so that
+        // `use`d names are visible to the whole module.
+        self.resolve_imports(&m.body);
+
         // Phase 2, register the bodies of all top-level
         self.resolve_top_level_contents(&m.body);
 
@@ -31,7 +106,9 @@ impl<'s> ModulePass<'s> for AstNameResolver<'s> {
         if !self.errs.is_empty() {
             Err(self.errs)
         } else {
-            Ok(self.scopes.finish_resolve())
+            let table = self.scopes.finish_resolve();
+            let warnings = unused_binding_pass(&table);
+            Ok(ResolvedModule { table, warnings })
         }
     }
 }
@@ -41,6 +118,18 @@ impl<'s> Default for AstNameResolver<'s> {
         Self {
             scopes: ScopedSymbolStack::default(),
             errs: Vec::new(),
+            modules: ModuleRegistry::default(),
+        }
+    }
+}
+
+impl<'s> AstNameResolver<'s> {
+    /// Build a resolver that can satisfy `use` items against already-resolved
+    /// modules in `modules`.
+    pub fn with_registry(modules: ModuleRegistry<'s>) -> Self {
+        Self {
+            modules,
+            ..Self::default()
         }
     }
 }
@@ -52,14 +141,55 @@ impl<'s> AstNameResolver<'s> {
             let nid = item.nid;
             match &item.kind {
                 ItemKind::Func(f) => {
-                    if let Err(e) = self.scopes.insert_func(nid, f.proto.name.span) {
-                        self.errs.push(e);
+                    if let Err(e) = self.scopes.insert_func(Namespace::Value, nid, f.proto.name.span)
+                    {
+                        self.errs.push(e.into());
                     }
                 }
                 ItemKind::Extern(proto) => {
                     // add function symbol to global
-                    if let Err(e) = self.scopes.insert_func(nid, proto.name.span) {
-                        self.errs.push(e);
+                    if let Err(e) =
+                        self.scopes.insert_func(Namespace::Value, nid, proto.name.span)
+                    {
+                        self.errs.push(e.into());
+                    }
+                }
+                // Imports are bound in a dedicated later phase.
+                ItemKind::Use(_) => (),
+            }
+        }
+    }
+
+    fn resolve_imports(&mut self, items: &[Item<'s>]) {
+        for item in items {
+            let ItemKind::Use(u) = &item.kind else {
+                continue;
+            };
+
+            let Some(table) = self.modules.get(u.module) else {
+                self.errs.push(AstResolutionErr::UnresolvedImport { span: u.span });
+                continue;
+            };
+
+            if u.glob {
+                // Bring in every public top-level name from the target module.
+                for (name, sid) in table.public_symbols(Namespace::Value) {
+                    if let Err(e) = self.scopes.insert_import(Namespace::Value, name, sid) {
+                        self.errs.push(e.into());
+                    }
+                }
+            } else {
+                // A single named symbol.
+                let name = self.scopes.text(u.name.span);
+                match table.lookup_public(Namespace::Value, name) {
+                    Some(sid) => {
+                        if let Err(e) = self.scopes.insert_import(Namespace::Value, name, sid) {
+                            self.errs.push(e.into());
+                        }
+                    }
+                    None => {
+                        self.errs
+                            .push(AstResolutionErr::UnresolvedImport { span: u.name.span });
                     }
                 }
             }
@@ -73,28 +203,55 @@ impl<'s> AstNameResolver<'s> {
                     self.resolve_func_contents(f);
                 }
                 ItemKind::Extern(_) => (),
+                ItemKind::Use(_) => (),
             }
         }
     }
 
     fn resolve_func_contents(&mut self, func: &Function<'s>) {
-        // extra scope required just for the function args
-        self.scopes.push_scope();
+        // The function boundary outer locals cannot be resolved across, with a
+        // dedicated inner scope holding just the parameters.
+        self.scopes.push_scope(ScopeKind::FunctionRoot);
+        self.scopes.push_scope(ScopeKind::FunctionArgs);
 
         for arg in &func.proto.args {
-            if let Err(e) = self.scopes.insert_local(arg.nid, arg.name.span) {
-                self.errs.push(e);
+            if let Err(e) = self.scopes.insert_local(Namespace::Value, arg.nid, arg.name.span) {
+                self.errs.push(e.into());
+            }
+            if let Some(anno) = &arg.ty {
+                self.resolve_type_anno(anno);
             }
         }
 
+        if let Some(ret) = &func.proto.ret_ty {
+            self.resolve_type_anno(ret);
+        }
+
         self.resolve_expr(&func.body);
 
         self.scopes.pop_scope();
+        self.scopes.pop_scope();
+    }
+
+    /// Resolve a type annotation in type position (argument / return types).
+    /// Type names live in their own namespace, so a value and a type may share
+    /// a spelling without colliding.
+    ///
+    /// Nothing populates the type namespace yet — there is no type-declaration
+    /// item and no seeded builtins — so a miss here is expected rather than an
+    /// error. We record a reuse when a name does resolve but stay silent on a
+    /// miss until type definitions exist; emitting `UnresolvedName` now would
+    /// reject every typed function.
+    fn resolve_type_anno(&mut self, anno: &TypeAnno<'s>) {
+        if let Some(sid_ref) = self.scopes.lookup(Namespace::Type, anno.span) {
+            let sid = *sid_ref;
+            self.scopes.insert_local_reuse(Namespace::Type, anno.nid, sid);
+        }
     }
     fn resolve_expr(&mut self, expr: &Expr<'s>) {
         match &*expr.kind {
             ExprKind::Block(b) => {
-                self.scopes.push_scope();
+                self.scopes.push_scope(ScopeKind::Block);
                 for sub in b {
                     self.resolve_expr(sub)
                 }
@@ -102,26 +259,56 @@ impl<'s> AstNameResolver<'s> {
             }
             ExprKind::Decl(vd) => {
                 self.resolve_expr(&vd.value);
-                if let Err(e) = self.scopes.insert_local(expr.nid, vd.bound.span) {
-                    self.errs.push(e);
+                if let Err(e) = self.scopes.insert_local(Namespace::Value, expr.nid, vd.bound.span)
+                {
+                    self.errs.push(e.into());
                 }
             }
             ExprKind::Let { bound, let_body } => {
-                // TODO WANT: use before definition should be acceptable in this block
-                // assuming no cycles
-                self.scopes.push_scope();
+                // Two phases, mirroring the top-level resolver: register every
+                // binding's name first so a value may refer to a later sibling
+                // (forward / mutually-recursive references), then resolve the
+                // value expressions and the body.
+                self.scopes.push_scope(ScopeKind::LetBindings);
+
+                for decl in bound {
+                    if let ExprKind::Decl(vd) = &*decl.kind {
+                        if let Err(e) =
+                            self.scopes.insert_local(Namespace::Value, decl.nid, vd.bound.span)
+                        {
+                            self.errs.push(e.into());
+                        }
+                    }
+                }
+
                 for decl in bound {
-                    self.resolve_expr(decl);
+                    match &*decl.kind {
+                        // Declarations are already in scope; resolve their value.
+                        ExprKind::Decl(vd) => self.resolve_expr(&vd.value),
+                        // Any non-declaration entry is still part of the block
+                        // and must be resolved like the baseline did.
+                        _ => self.resolve_expr(decl),
+                    }
                 }
+
+                // Forward references are fine, but genuine cycles are not.
+                self.check_binding_cycles(bound);
+
                 self.resolve_expr(let_body);
                 self.scopes.pop_scope();
             }
-            ExprKind::Var(td) => match self.scopes.lookup(td.span) {
+            ExprKind::Var(td) => match self.scopes.lookup(Namespace::Value, td.span) {
                 Some(sid_ref) => {
                     let sid = *sid_ref;
-                    self.scopes.insert_local_reuse(expr.nid, sid);
+                    self.scopes.insert_local_reuse(Namespace::Value, expr.nid, sid);
+                }
+                None => {
+                    let suggestion = self.suggest(Namespace::Value, td.span);
+                    self.errs.push(AstResolutionErr::UnresolvedName {
+                        span: td.span,
+                        suggestion,
+                    });
                 }
-                None => unimplemented!("Unimplemented use before def"),
             },
             ExprKind::Call { callee, args } => {
                 for arg in args {
@@ -145,9 +332,269 @@ impl<'s> AstNameResolver<'s> {
             }
             ExprKind::While { cond, while_body } => {
                 self.resolve_expr(cond);
+                self.scopes.push_scope(ScopeKind::LoopBody);
                 self.resolve_expr(while_body);
+                self.scopes.pop_scope();
+            }
+            ExprKind::Lit(_) => (),
+        }
+    }
+
+    /// Detect dependency cycles among a `let`'s sibling bindings. Nodes are the
+    /// bindings; an edge points from a binding to each sibling its value reads.
+    /// Any strongly-connected component larger than one node, or a
+    /// self-referential non-function binding, is an illegal cycle.
+    fn check_binding_cycles(&mut self, bound: &[Expr<'s>]) {
+        let mut spans: Vec<Span> = Vec::new();
+        let mut names: Vec<&'s str> = Vec::new();
+        let mut values: Vec<&Expr<'s>> = Vec::new();
+        for decl in bound {
+            if let ExprKind::Decl(vd) = &*decl.kind {
+                spans.push(vd.bound.span);
+                names.push(self.scopes.text(vd.bound.span));
+                values.push(&vd.value);
+            }
+        }
+
+        let n = names.len();
+        let mut adj: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for (i, value) in values.iter().enumerate() {
+            // Only references that are *free* in the value (not shadowed by an
+            // inner `Decl`/`Let`) can name a sibling. Re-resolving every `Var`
+            // span by text after the fact would wrongly bind an inner shadow to
+            // its sibling, inventing cycles in programs like
+            // `let a = { b = 1; b }; b = a in b`.
+            let mut refs = Vec::new();
+            self.collect_free_refs(value, &mut Vec::new(), &mut refs);
+            for rspan in refs {
+                let rtext = self.scopes.text(rspan);
+                if let Some(j) = names.iter().position(|name| *name == rtext) {
+                    if !adj[i].contains(&j) {
+                        adj[i].push(j);
+                    }
+                }
+            }
+        }
+
+        for comp in tarjan_scc(&adj) {
+            if comp.len() > 1 {
+                self.errs.push(AstResolutionErr::RecursiveBindingCycle {
+                    spans: comp.iter().map(|&i| spans[i]).collect(),
+                });
+            } else {
+                // A singleton that points at itself: legal only for a function
+                // binding (recursion). No function-literal expression kind
+                // exists yet, so every self-loop is currently an error.
+                let i = comp[0];
+                if adj[i].contains(&i) {
+                    self.errs.push(AstResolutionErr::RecursiveBindingCycle {
+                        spans: vec![spans[i]],
+                    });
+                }
+            }
+        }
+    }
+
+    /// Find the in-scope identifier closest to the unresolved name at `target`,
+    /// for a "did you mean `foo`?" hint. Candidates are every name currently
+    /// reachable from the scope stack; the best is accepted only when its edit
+    /// distance is within `max(1, len / 3)`, so short names demand a near-exact
+    /// match while typos in longer names are tolerated.
+    fn suggest(&self, ns: Namespace, target: Span) -> Option<Span> {
+        let needle = self.scopes.text(target);
+        let limit = (needle.len() / 3).max(1);
+
+        let mut best: Option<(usize, Span)> = None;
+        for cand in self.scopes.live_names(ns) {
+            let dist = levenshtein(needle, self.scopes.text(cand));
+            if dist == 0 {
+                continue;
+            }
+            if dist <= limit && best.map_or(true, |(bd, _)| dist < bd) {
+                best = Some((dist, cand));
+            }
+        }
+
+        best.map(|(_, span)| span)
+    }
+
+    /// Collect the spans of the `Var` references that are *free* in `expr`,
+    /// i.e. not bound by an inner `Decl` or `Let` along the way. `bound` carries
+    /// the names introduced by enclosing inner scopes as the walk descends.
+    /// Used to build a `let` block's dependency graph without mistaking an inner
+    /// shadow of a sibling's name for a reference to that sibling.
+    fn collect_free_refs(&self, expr: &Expr<'s>, bound: &mut Vec<&'s str>, out: &mut Vec<Span>) {
+        match &*expr.kind {
+            ExprKind::Var(td) => {
+                let text = self.scopes.text(td.span);
+                if !bound.iter().any(|b| *b == text) {
+                    out.push(td.span);
+                }
+            }
+            ExprKind::Block(b) => {
+                let mark = bound.len();
+                for sub in b {
+                    // A `Decl`'s value is resolved before its own name is in
+                    // scope, so walk first and only then shadow the name for the
+                    // rest of the block.
+                    self.collect_free_refs(sub, bound, out);
+                    if let ExprKind::Decl(vd) = &*sub.kind {
+                        bound.push(self.scopes.text(vd.bound.span));
+                    }
+                }
+                bound.truncate(mark);
+            }
+            ExprKind::Decl(vd) => self.collect_free_refs(&vd.value, bound, out),
+            ExprKind::Let {
+                bound: inner,
+                let_body,
+            } => {
+                let mark = bound.len();
+                for decl in inner {
+                    if let ExprKind::Decl(vd) = &*decl.kind {
+                        bound.push(self.scopes.text(vd.bound.span));
+                    }
+                }
+                for decl in inner {
+                    self.collect_free_refs(decl, bound, out);
+                }
+                self.collect_free_refs(let_body, bound, out);
+                bound.truncate(mark);
+            }
+            ExprKind::Call { callee, args } => {
+                self.collect_free_refs(callee, bound, out);
+                for arg in args {
+                    self.collect_free_refs(arg, bound, out);
+                }
+            }
+            ExprKind::Binary { lhs, rhs, .. } => {
+                self.collect_free_refs(lhs, bound, out);
+                self.collect_free_refs(rhs, bound, out);
+            }
+            ExprKind::If {
+                cond,
+                if_body,
+                else_body,
+            } => {
+                self.collect_free_refs(cond, bound, out);
+                self.collect_free_refs(if_body, bound, out);
+                self.collect_free_refs(else_body, bound, out);
+            }
+            ExprKind::While { cond, while_body } => {
+                self.collect_free_refs(cond, bound, out);
+                self.collect_free_refs(while_body, bound, out);
             }
             ExprKind::Lit(_) => (),
         }
     }
 }
+
+/// Report every local or function argument that was introduced but never read.
+///
+/// Resolution already records a use against a symbol each time an `ExprKind::Var`
+/// resolves to it (via `insert_local_reuse`), so this is a cheap sweep over the
+/// finished table rather than another AST traversal. Bindings whose name begins
+/// with an underscore are exempt, matching the usual "intentionally unused"
+/// convention.
+fn unused_binding_pass(table: &ModSymTable) -> Vec<AstResolutionWarning> {
+    let mut warnings = Vec::new();
+    for sym in table.symbols() {
+        if !matches!(sym.kind, SymbolKind::Local | SymbolKind::Arg) {
+            continue;
+        }
+        if sym.uses > 0 {
+            continue;
+        }
+        if table.text(sym.span).starts_with('_') {
+            continue;
+        }
+        warnings.push(AstResolutionWarning::UnusedBinding { span: sym.span });
+    }
+    warnings
+}
+
+/// Tarjan's strongly-connected-components algorithm over an adjacency list.
+/// Returns one vector of node indices per component.
+fn tarjan_scc(adj: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut state = Tarjan {
+        adj,
+        index: 0,
+        indices: vec![None; adj.len()],
+        low: vec![0; adj.len()],
+        on_stack: vec![false; adj.len()],
+        stack: Vec::new(),
+        sccs: Vec::new(),
+    };
+    for v in 0..adj.len() {
+        if state.indices[v].is_none() {
+            state.connect(v);
+        }
+    }
+    state.sccs
+}
+
+struct Tarjan<'a> {
+    adj: &'a [Vec<usize>],
+    index: usize,
+    indices: Vec<Option<usize>>,
+    low: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    sccs: Vec<Vec<usize>>,
+}
+
+impl Tarjan<'_> {
+    fn connect(&mut self, v: usize) {
+        self.indices[v] = Some(self.index);
+        self.low[v] = self.index;
+        self.index += 1;
+        self.stack.push(v);
+        self.on_stack[v] = true;
+
+        for k in 0..self.adj[v].len() {
+            let w = self.adj[v][k];
+            match self.indices[w] {
+                None => {
+                    self.connect(w);
+                    self.low[v] = self.low[v].min(self.low[w]);
+                }
+                Some(wi) if self.on_stack[w] => {
+                    self.low[v] = self.low[v].min(wi);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if self.low[v] == self.indices[v].unwrap() {
+            let mut comp = Vec::new();
+            loop {
+                let w = self.stack.pop().unwrap();
+                self.on_stack[w] = false;
+                comp.push(w);
+                if w == v {
+                    break;
+                }
+            }
+            self.sccs.push(comp);
+        }
+    }
+}
+
+/// Classic dynamic-programming Levenshtein edit distance over the bytes of
+/// `a` and `b`, using two rolling rows.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+
+    for (i, ca) in a.bytes().enumerate() {
+        cur[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    prev[b.len()]
+}